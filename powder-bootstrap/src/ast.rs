@@ -19,11 +19,20 @@ impl Ast for Definition {}
 
 #[derive(Debug)]
 pub struct Function {
-	pub name: String,
-	pub body: Block,
+	pub name:        String,
+	pub parameters:  Vec<Parameter>,
+	pub return_type: Option<Type>,
+	pub body:        Block,
 }
 impl Ast for Function {}
 
+#[derive(Debug)]
+pub struct Parameter {
+	pub name:  String,
+	pub type_: Type,
+}
+impl Ast for Parameter {}
+
 #[derive(Debug)]
 pub struct Block {
 	pub statements: Vec<Statement>,
@@ -46,12 +55,24 @@ impl Ast for Statement {}
 #[derive(Debug)]
 pub enum Expression {
 	NaturalLiteral(i128),
+	FloatLiteral(f64),
+	BooleanLiteral(bool),
 	UnaryOperation(UnaryOperator, Box<Expression>),
 	BinaryOperation {
 		operator: BinaryOperator,
 		lhs: Box<Expression>,
 		rhs: Box<Expression>,
 	},
+	If {
+		condition:  Box<Expression>,
+		then_block: Box<Block>,
+		else_block: Option<Box<Block>>,
+	},
+	Variable(String),
+	Call {
+		callee:    String,
+		arguments: Vec<Expression>,
+	},
 }
 impl Ast for Expression {}
 
@@ -77,6 +98,7 @@ impl UnaryOperator {
 		match token_type {
 			TokenType::Plus => Some(Self::Plus),
 			TokenType::Minus => Some(Self::Minus),
+			TokenType::Bang => Some(Self::Not),
 			_ => None,
 		}
 	}
@@ -88,6 +110,13 @@ pub enum BinaryOperator {
 	Subtract,
 	Multiply,
 	Divide,
+	Equal,
+	LessThan,
+	GreaterThan,
+	LessEqual,
+	GreaterEqual,
+	And,
+	Or,
 }
 
 impl BinaryOperator {
@@ -96,7 +125,14 @@ impl BinaryOperator {
 			TokenType::Plus => Some(Self::Add),
 			TokenType::Minus => Some(Self::Subtract),
 			TokenType::Star => Some(Self::Multiply),
-			// TODO: Parse / individually.
+			TokenType::Slash => Some(Self::Divide),
+			TokenType::EqualsEquals => Some(Self::Equal),
+			TokenType::LessThan => Some(Self::LessThan),
+			TokenType::GreaterThan => Some(Self::GreaterThan),
+			TokenType::LessThanEquals => Some(Self::LessEqual),
+			TokenType::GreaterThanEquals => Some(Self::GreaterEqual),
+			TokenType::AmpersandAmpersand => Some(Self::And),
+			TokenType::PipePipe => Some(Self::Or),
 			_ => None,
 		}
 	}
@@ -105,4 +141,6 @@ impl BinaryOperator {
 #[derive(Debug)]
 pub enum Type {
 	N64,
+	F64,
+	Bool,
 }
@@ -0,0 +1,316 @@
+//! Code-generation backends that lower the Powder AST into target source code.
+use crate::ast::{
+	BinaryOperator, Block, Definition, Expression, File, Function, Statement, Type, UnaryOperator, VariableKind,
+};
+use crate::error::{CompileError, CompileErrorKind, Position};
+
+/// A backend that lowers a parsed [`File`] into some target source language.
+///
+/// Each backend (C, and eventually JS/LLVM/x86) implements this trait over the same AST node types.
+pub trait Generator {
+	/// Generate target source code for the given file.
+	fn generate(&mut self, file: &File) -> Result<String, CompileError>;
+}
+
+/// The binding power of an operand relative to its containing unary operator, used to decide
+/// whether a nested binary operation needs parentheses.
+const UNARY_OPERAND_PRECEDENCE: u8 = 6;
+
+/// Lowers Powder into C source that relies on GNU statement expressions (`({ ... })`) to let `if` be used as a
+/// value, so it must be compiled with GNU extensions enabled (e.g. `-std=gnu99`) rather than strict C99.
+#[derive(Debug, Default)]
+pub struct CGenerator {
+	output: String,
+}
+
+impl CGenerator {
+	fn generate_definition(&mut self, definition: &Definition) -> Result<(), CompileError> {
+		match definition {
+			Definition::Function(function) => self.generate_function(function),
+			Definition::Statement(Statement::VariableDeclaration { kind, name, type_, initial_value }) =>
+				self.generate_variable_declaration(kind, name, type_, initial_value.as_ref()),
+			Definition::Statement(other) => Err(CompileError::new(
+				CompileErrorKind::Unsupported,
+				Position::start(),
+				format!("Top-level statement {:?} cannot be generated; only variable declarations are allowed", other),
+			)),
+		}
+	}
+
+	fn generate_function(&mut self, function: &Function) -> Result<(), CompileError> {
+		self.output.push_str(function.return_type.as_ref().map_or("void", Self::generate_type));
+		self.output.push(' ');
+		self.output.push_str(&function.name);
+		self.output.push('(');
+		if function.parameters.is_empty() {
+			self.output.push_str("void");
+		} else {
+			for (index, parameter) in function.parameters.iter().enumerate() {
+				if index > 0 {
+					self.output.push_str(", ");
+				}
+				self.output.push_str(Self::generate_type(&parameter.type_));
+				self.output.push(' ');
+				self.output.push_str(&parameter.name);
+			}
+		}
+		self.output.push_str(") ");
+		self.generate_block(&function.name, &function.body, function.return_type.is_some())?;
+		self.output.push('\n');
+		Ok(())
+	}
+
+	/// Render a function body, reconciling `has_return_type` (whether the function declares a return type) with
+	/// whether `block` actually has a trailing value: a value with no declared return type would emit a `void`
+	/// function that still `return`s a value, and a declared return type with no trailing value would emit a
+	/// non-`void` function that falls off the end without returning anything. Both are rejected here rather than
+	/// silently generating C that fails to compile or misbehaves at runtime.
+	fn generate_block(&mut self, function_name: &str, block: &Block, has_return_type: bool) -> Result<(), CompileError> {
+		self.output.push_str("{\n");
+		for statement in &block.statements {
+			self.generate_statement(statement)?;
+		}
+		match (&block.value, has_return_type) {
+			(Some(value), true) => {
+				self.output.push_str("\treturn ");
+				let value_code = self.generate_expression(value, 0)?;
+				self.output.push_str(&value_code);
+				self.output.push_str(";\n");
+			},
+			(Some(_), false) =>
+				return Err(CompileError::new(
+					CompileErrorKind::Unsupported,
+					Position::start(),
+					format!("Function '{}' has a value-yielding body but no declared return type", function_name),
+				)),
+			(None, true) =>
+				return Err(CompileError::new(
+					CompileErrorKind::Unsupported,
+					Position::start(),
+					format!("Function '{}' declares a return type but its body has no trailing value", function_name),
+				)),
+			(None, false) => {},
+		}
+		self.output.push_str("}\n");
+		Ok(())
+	}
+
+	/// Render `block` as a GNU statement expression `({ ...statements...; value; })` whose value is the block's
+	/// trailing expression (or `0` if the block has none), in a scratch generator, for embedding directly inside
+	/// a C expression.
+	fn generate_value_block(&self, block: &Block) -> Result<String, CompileError> {
+		let mut nested = Self::default();
+		nested.output.push_str("({\n");
+		for statement in &block.statements {
+			nested.generate_statement(statement)?;
+		}
+		let value_code = match &block.value {
+			Some(value) => nested.generate_expression(value, 0)?,
+			None => "0".to_string(),
+		};
+		nested.output.push('\t');
+		nested.output.push_str(&value_code);
+		nested.output.push_str(";\n})");
+		Ok(nested.output)
+	}
+
+	fn generate_statement(&mut self, statement: &Statement) -> Result<(), CompileError> {
+		match statement {
+			Statement::Expression(expression) => {
+				self.output.push('\t');
+				let code = self.generate_expression(expression, 0)?;
+				self.output.push_str(&code);
+				self.output.push_str(";\n");
+				Ok(())
+			},
+			Statement::VariableDeclaration { kind, name, type_, initial_value } =>
+				self.generate_variable_declaration(kind, name, type_, initial_value.as_ref()),
+		}
+	}
+
+	fn generate_variable_declaration(
+		&mut self,
+		kind: &VariableKind,
+		name: &str,
+		type_: &Type,
+		initial_value: Option<&Expression>,
+	) -> Result<(), CompileError> {
+		self.output.push('\t');
+		if matches!(kind, VariableKind::Immutable) {
+			self.output.push_str("const ");
+		}
+		self.output.push_str(Self::generate_type(type_));
+		self.output.push(' ');
+		self.output.push_str(name);
+		if let Some(initial_value) = initial_value {
+			let value_code = self.generate_expression(initial_value, 0)?;
+			self.output.push_str(" = ");
+			self.output.push_str(&value_code);
+		}
+		self.output.push_str(";\n");
+		Ok(())
+	}
+
+	const fn generate_type(type_: &Type) -> &'static str {
+		match type_ {
+			Type::N64 => "uint64_t",
+			Type::F64 => "double",
+			Type::Bool => "bool",
+		}
+	}
+
+	/// Lower an expression to C, wrapping it in parentheses if its outermost operator binds looser than
+	/// `min_precedence` requires.
+	fn generate_expression(&self, expression: &Expression, min_precedence: u8) -> Result<String, CompileError> {
+		match expression {
+			Expression::NaturalLiteral(value) => Ok(value.to_string()),
+			Expression::FloatLiteral(value) => Ok(value.to_string()),
+			Expression::BooleanLiteral(value) => Ok(value.to_string()),
+			Expression::UnaryOperation(operator, operand) => {
+				let operator_str = Self::unary_operator_str(operator);
+				// A nested unary operator of the same character (`- -5`, `+ +5`) would otherwise be emitted
+				// back-to-back as `--5`/`++5`, which C parses as the decrement/increment operator applied to an
+				// rvalue rather than as two separate unary operators, and gcc rejects it.
+				let needs_separator =
+					matches!(&**operand, Expression::UnaryOperation(inner, _) if Self::unary_operator_str(inner) == operator_str);
+				Ok(format!(
+					"{}{}{}",
+					operator_str,
+					if needs_separator { " " } else { "" },
+					self.generate_expression(operand, UNARY_OPERAND_PRECEDENCE)?
+				))
+			},
+			Expression::BinaryOperation { operator, lhs, rhs } => {
+				let precedence = Self::binary_precedence(operator);
+				let code = format!(
+					"{} {} {}",
+					self.generate_expression(lhs, precedence)?,
+					Self::binary_operator_str(operator),
+					self.generate_expression(rhs, precedence + 1)?,
+				);
+				Ok(if precedence < min_precedence { format!("({})", code) } else { code })
+			},
+			Expression::If { condition, then_block, else_block } => {
+				let condition_code = self.generate_expression(condition, 0)?;
+				let then_code = self.generate_value_block(then_block)?;
+				let else_code = match else_block {
+					Some(else_block) => self.generate_value_block(else_block)?,
+					None => "({ 0; })".to_string(),
+				};
+				// Lowered as a ternary over GNU statement expressions (rather than a GNU statement expression
+				// wrapping a bare `if`-statement) so the `if` can be used as a value, matching its role as a
+				// block's trailing expression: a `({ ... })` only yields a value when the last thing inside it
+				// is an expression statement, and a bare `if`/`else` is not one.
+				Ok(format!("({} ? {} : {})", condition_code, then_code, else_code))
+			},
+			Expression::Variable(name) => Ok(name.clone()),
+			Expression::Call { callee, arguments } => {
+				let argument_code =
+					arguments.iter().map(|argument| self.generate_expression(argument, 0)).collect::<Result<Vec<_>, _>>()?;
+				Ok(format!("{}({})", callee, argument_code.join(", ")))
+			},
+		}
+	}
+
+	const fn binary_precedence(operator: &BinaryOperator) -> u8 {
+		match operator {
+			BinaryOperator::Or => 1,
+			BinaryOperator::And => 2,
+			BinaryOperator::Equal
+			| BinaryOperator::LessThan
+			| BinaryOperator::GreaterThan
+			| BinaryOperator::LessEqual
+			| BinaryOperator::GreaterEqual => 3,
+			BinaryOperator::Add | BinaryOperator::Subtract => 4,
+			BinaryOperator::Multiply | BinaryOperator::Divide => 5,
+		}
+	}
+
+	const fn binary_operator_str(operator: &BinaryOperator) -> &'static str {
+		match operator {
+			BinaryOperator::Add => "+",
+			BinaryOperator::Subtract => "-",
+			BinaryOperator::Multiply => "*",
+			BinaryOperator::Divide => "/",
+			BinaryOperator::Equal => "==",
+			BinaryOperator::LessThan => "<",
+			BinaryOperator::GreaterThan => ">",
+			BinaryOperator::LessEqual => "<=",
+			BinaryOperator::GreaterEqual => ">=",
+			BinaryOperator::And => "&&",
+			BinaryOperator::Or => "||",
+		}
+	}
+
+	const fn unary_operator_str(operator: &UnaryOperator) -> &'static str {
+		match operator {
+			UnaryOperator::Plus => "+",
+			UnaryOperator::Minus => "-",
+			UnaryOperator::Not => "!",
+			UnaryOperator::Reference => "&",
+			UnaryOperator::Dereference => "*",
+		}
+	}
+}
+
+impl Generator for CGenerator {
+	fn generate(&mut self, file: &File) -> Result<String, CompileError> {
+		self.output.clear();
+		// The generated types `uint64_t` and `bool` come from these headers, so the output needs them to compile
+		// standalone rather than merely as a fragment pasted into an already-prepared translation unit.
+		self.output.push_str("#include <stdint.h>\n#include <stdbool.h>\n\n");
+		for definition in &file.definitions {
+			self.generate_definition(definition)?;
+		}
+		Ok(std::mem::take(&mut self.output))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{lexer, parser};
+
+	fn generate_c(code: &str) -> Result<String, CompileError> {
+		let tokens = lexer::lex(code).unwrap();
+		let file = parser::parse(tokens).unwrap();
+		CGenerator::default().generate(&file)
+	}
+
+	#[test]
+	fn generate_includes_stdint_and_stdbool() {
+		let source = generate_c("function f(): n64 { 1 }").unwrap();
+		assert!(source.contains("#include <stdint.h>"), "missing stdint.h include: {}", source);
+		assert!(source.contains("#include <stdbool.h>"), "missing stdbool.h include: {}", source);
+	}
+
+	#[test]
+	fn nested_unary_minus_does_not_collide_into_decrement() {
+		let source = generate_c("function f(): n64 { - -5 }").unwrap();
+		assert!(!source.contains("--5"), "nested unary minus must not read as C's '--' operator: {}", source);
+	}
+
+	#[test]
+	fn nested_unary_plus_does_not_collide_into_increment() {
+		let source = generate_c("function f(): n64 { + +5 }").unwrap();
+		assert!(!source.contains("++5"), "nested unary plus must not read as C's '++' operator: {}", source);
+	}
+
+	#[test]
+	fn value_yielding_body_without_a_return_type_is_rejected() {
+		let error = generate_c("function f() { 5 }").unwrap_err();
+		assert_eq!(error.kind, CompileErrorKind::Unsupported);
+	}
+
+	#[test]
+	fn declared_return_type_without_a_trailing_value_is_rejected() {
+		let error = generate_c("function f(): n64 { const x: n64 = 1; }").unwrap_err();
+		assert_eq!(error.kind, CompileErrorKind::Unsupported);
+	}
+
+	#[test]
+	fn matching_return_type_and_trailing_value_generates_a_return_statement() {
+		let source = generate_c("function f(): n64 { 5 }").unwrap();
+		assert!(source.contains("uint64_t f(void) {\n\treturn 5;\n}"), "unexpected output: {}", source);
+	}
+}
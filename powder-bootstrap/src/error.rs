@@ -0,0 +1,57 @@
+//! Shared source-position and error types used by the lexer and parser.
+use std::fmt::{Display, Formatter};
+
+/// A 1-based line and column location in a source file.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub struct Position {
+	pub line:   usize,
+	pub column: usize,
+}
+
+impl Position {
+	/// The position of the very first character of a file.
+	pub const fn start() -> Self {
+		Self { line: 1, column: 1 }
+	}
+}
+
+impl Display for Position {
+	fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+		write!(f, "{}:{}", self.line, self.column)
+	}
+}
+
+/// What kind of problem was encountered while lexing or parsing.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum CompileErrorKind {
+	/// A token was found where a different one was expected.
+	UnexpectedToken,
+	/// The token stream ended before parsing could complete.
+	UnexpectedEof,
+	/// A literal's text could not be converted into its value.
+	InvalidLiteral,
+	/// A valid AST node was encountered that the current stage cannot handle.
+	Unsupported,
+}
+
+/// An error with an attached source position, produced while lexing or parsing.
+#[derive(Debug, Clone)]
+pub struct CompileError {
+	pub kind:     CompileErrorKind,
+	pub position: Position,
+	pub message:  String,
+}
+
+impl CompileError {
+	pub fn new(kind: CompileErrorKind, position: Position, message: impl Into<String>) -> Self {
+		Self { kind, position, message: message.into() }
+	}
+}
+
+impl Display for CompileError {
+	fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+		write!(f, "error at {}: {}", self.position, self.message)
+	}
+}
+
+impl std::error::Error for CompileError {}
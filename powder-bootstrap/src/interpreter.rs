@@ -0,0 +1,341 @@
+//! A tree-walking interpreter that executes a parsed [`File`] directly, without any code generation.
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use crate::ast::{BinaryOperator, Block, Definition, Expression, File, Function, Statement, UnaryOperator, VariableKind};
+
+/// A runtime value produced by evaluating an expression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+	/// A signed integer value.
+	Natural(i128),
+	/// A floating-point value.
+	Real(f64),
+	/// A boolean value.
+	Boolean(bool),
+	/// The value of a block with no trailing expression, analogous to Rust's `()`.
+	Unit,
+}
+
+/// An error raised while executing a program, as opposed to while lexing or parsing it.
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+	pub message: String,
+}
+
+impl RuntimeError {
+	fn new(message: impl Into<String>) -> Self {
+		Self { message: message.into() }
+	}
+}
+
+impl Display for RuntimeError {
+	fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+		write!(f, "runtime error: {}", self.message)
+	}
+}
+
+impl std::error::Error for RuntimeError {}
+
+/// A variable binding: its current value (`None` if declared but never initialized) and whether it may be rebound.
+#[derive(Debug, Clone)]
+struct Binding {
+	value:   Option<Value>,
+	mutable: bool,
+}
+
+/// A stack of lexical scopes mapping variable names to their current value.
+#[derive(Debug)]
+pub struct Environment {
+	scopes: Vec<HashMap<String, Binding>>,
+}
+
+impl Default for Environment {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl Environment {
+	pub fn new() -> Self {
+		Self { scopes: vec![HashMap::new()] }
+	}
+
+	pub fn push_scope(&mut self) {
+		self.scopes.push(HashMap::new());
+	}
+
+	pub fn pop_scope(&mut self) {
+		self.scopes.pop();
+	}
+
+	/// Bind `name` to `value` in the innermost scope, rejecting an attempt to rebind an existing immutable name.
+	pub fn declare(&mut self, name: String, value: Option<Value>, mutable: bool) -> Result<(), RuntimeError> {
+		let scope = self.scopes.last_mut().expect("Environment must always have at least one scope");
+		if let Some(existing) = scope.get(&name) && !existing.mutable {
+			return Err(RuntimeError::new(format!("Cannot reassign immutable variable '{}'", name)));
+		}
+		scope.insert(name, Binding { value, mutable });
+		Ok(())
+	}
+
+	/// Look up `name`, walking scopes from innermost to outermost.
+	pub fn get(&self, name: &str) -> Result<Value, RuntimeError> {
+		for scope in self.scopes.iter().rev() {
+			if let Some(binding) = scope.get(name) {
+				return binding.value.ok_or_else(|| RuntimeError::new(format!("Variable '{}' is uninitialized", name)));
+			}
+		}
+		Err(RuntimeError::new(format!("Undefined variable '{}'", name)))
+	}
+}
+
+/// Executes a parsed program by walking its AST, maintaining an [`Environment`] of live variables as it goes.
+#[derive(Debug, Default)]
+pub struct Interpreter<'a> {
+	environment: Environment,
+	/// Functions defined in the program, keyed by name, so calls can be resolved while evaluating expressions.
+	functions:   HashMap<&'a str, &'a Function>,
+}
+
+impl<'a> Interpreter<'a> {
+	pub fn new() -> Self {
+		Self { environment: Environment::new(), functions: HashMap::new() }
+	}
+
+	/// Register every top-level function, then run every top-level statement in the file in order.
+	pub fn run(&mut self, file: &'a File) -> Result<(), RuntimeError> {
+		for definition in &file.definitions {
+			if let Definition::Function(function) = definition {
+				self.functions.insert(&function.name, function);
+			}
+		}
+		for definition in &file.definitions {
+			if let Definition::Statement(statement) = definition {
+				self.execute(statement)?;
+			}
+		}
+		Ok(())
+	}
+
+	/// Call a function by name with already-evaluated argument values, running its body in a fresh scope.
+	///
+	/// Powder is lexically scoped: the callee only ever sees the global scope plus its own parameters, never
+	/// whatever local scopes happen to be on the stack at the call site. We enforce that by setting aside the
+	/// caller's local scopes for the duration of the call and restoring them afterwards.
+	fn call_function(&mut self, name: &str, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+		let function = *self.functions.get(name).ok_or_else(|| RuntimeError::new(format!("Undefined function '{}'", name)))?;
+		if function.parameters.len() != arguments.len() {
+			return Err(RuntimeError::new(format!(
+				"Function '{}' expects {} argument(s), but {} were given",
+				name,
+				function.parameters.len(),
+				arguments.len()
+			)));
+		}
+
+		let caller_scopes = self.environment.scopes.split_off(1);
+		self.environment.push_scope();
+		// Bind via try_for_each/and_then rather than `?` so a failed declaration (e.g. a duplicate parameter
+		// name) still falls through to popping the callee's scope and restoring the caller's below.
+		let result = function
+			.parameters
+			.iter()
+			.zip(arguments)
+			.try_for_each(|(parameter, value)| self.environment.declare(parameter.name.clone(), Some(value), false))
+			.and_then(|()| self.eval_block_body(&function.body));
+		self.environment.pop_scope();
+		self.environment.scopes.extend(caller_scopes);
+		result
+	}
+
+	fn execute(&mut self, statement: &Statement) -> Result<(), RuntimeError> {
+		match statement {
+			Statement::Expression(expression) => self.eval(expression).map(|_| ()),
+			Statement::VariableDeclaration { kind, name, initial_value, .. } => {
+				let value = initial_value.as_ref().map(|expression| self.eval(expression)).transpose()?;
+				self.environment.declare(name.clone(), value, matches!(kind, VariableKind::Mutable))
+			},
+		}
+	}
+
+	/// Evaluate an expression to a runtime [`Value`].
+	pub fn eval(&mut self, expression: &Expression) -> Result<Value, RuntimeError> {
+		match expression {
+			Expression::NaturalLiteral(value) => Ok(Value::Natural(*value)),
+			Expression::FloatLiteral(value) => Ok(Value::Real(*value)),
+			Expression::BooleanLiteral(value) => Ok(Value::Boolean(*value)),
+			Expression::UnaryOperation(operator, operand) => match operator {
+				UnaryOperator::Plus => Ok(Value::Natural(self.eval_natural(operand)?)),
+				UnaryOperator::Minus => Ok(Value::Natural(-self.eval_natural(operand)?)),
+				UnaryOperator::Not => Ok(Value::Boolean(!self.eval_condition(operand)?)),
+				UnaryOperator::Reference | UnaryOperator::Dereference => Err(RuntimeError::new(format!(
+					"Unary operator {:?} is not yet supported by the interpreter",
+					operator
+				))),
+			},
+			Expression::BinaryOperation { operator, lhs, rhs } => match operator {
+				BinaryOperator::Add | BinaryOperator::Subtract | BinaryOperator::Multiply | BinaryOperator::Divide => {
+					let lhs = self.eval_natural(lhs)?;
+					let rhs = self.eval_natural(rhs)?;
+					match operator {
+						BinaryOperator::Add => Ok(Value::Natural(lhs + rhs)),
+						BinaryOperator::Subtract => Ok(Value::Natural(lhs - rhs)),
+						BinaryOperator::Multiply => Ok(Value::Natural(lhs * rhs)),
+						BinaryOperator::Divide =>
+							if rhs == 0 {
+								Err(RuntimeError::new("Division by zero"))
+							} else {
+								Ok(Value::Natural(lhs / rhs))
+							},
+						_ => unreachable!("matched above"),
+					}
+				},
+				BinaryOperator::Equal
+				| BinaryOperator::LessThan
+				| BinaryOperator::GreaterThan
+				| BinaryOperator::LessEqual
+				| BinaryOperator::GreaterEqual => {
+					let lhs = self.eval_natural(lhs)?;
+					let rhs = self.eval_natural(rhs)?;
+					Ok(Value::Boolean(match operator {
+						BinaryOperator::Equal => lhs == rhs,
+						BinaryOperator::LessThan => lhs < rhs,
+						BinaryOperator::GreaterThan => lhs > rhs,
+						BinaryOperator::LessEqual => lhs <= rhs,
+						BinaryOperator::GreaterEqual => lhs >= rhs,
+						_ => unreachable!("matched above"),
+					}))
+				},
+				// Short-circuiting: the right-hand side is only evaluated when its result can affect the outcome.
+				BinaryOperator::And =>
+					if self.eval_condition(lhs)? {
+						Ok(Value::Boolean(self.eval_condition(rhs)?))
+					} else {
+						Ok(Value::Boolean(false))
+					},
+				BinaryOperator::Or =>
+					if self.eval_condition(lhs)? {
+						Ok(Value::Boolean(true))
+					} else {
+						Ok(Value::Boolean(self.eval_condition(rhs)?))
+					},
+			},
+			Expression::If { condition, then_block, else_block } =>
+				if self.eval_condition(condition)? {
+					self.eval_block(then_block)
+				} else if let Some(else_block) = else_block {
+					self.eval_block(else_block)
+				} else {
+					Ok(Value::Unit)
+				},
+			Expression::Variable(name) => self.environment.get(name),
+			Expression::Call { callee, arguments } => {
+				let argument_values = arguments.iter().map(|argument| self.eval(argument)).collect::<Result<Vec<_>, _>>()?;
+				self.call_function(callee, argument_values)
+			},
+		}
+	}
+
+	/// Evaluate an expression, requiring it to produce a [`Value::Natural`].
+	fn eval_natural(&mut self, expression: &Expression) -> Result<i128, RuntimeError> {
+		match self.eval(expression)? {
+			Value::Natural(value) => Ok(value),
+			other => Err(RuntimeError::new(format!("Expected a natural number, found {:?}", other))),
+		}
+	}
+
+	/// Evaluate an expression, requiring it to produce a [`Value::Boolean`].
+	fn eval_condition(&mut self, expression: &Expression) -> Result<bool, RuntimeError> {
+		match self.eval(expression)? {
+			Value::Boolean(value) => Ok(value),
+			other => Err(RuntimeError::new(format!("Expected a boolean condition, found {:?}", other))),
+		}
+	}
+
+	/// Run a block in its own scope and return its resulting value (its trailing expression, or [`Value::Unit`]).
+	fn eval_block(&mut self, block: &Block) -> Result<Value, RuntimeError> {
+		self.environment.push_scope();
+		let result = self.eval_block_body(block);
+		self.environment.pop_scope();
+		result
+	}
+
+	fn eval_block_body(&mut self, block: &Block) -> Result<Value, RuntimeError> {
+		for statement in &block.statements {
+			self.execute(statement)?;
+		}
+		block.value.as_ref().map_or(Ok(Value::Unit), |value| self.eval(value))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::{lexer, parser};
+
+	fn parse_file(code: &str) -> File {
+		let tokens = lexer::lex(code).unwrap();
+		parser::parse(tokens).unwrap()
+	}
+
+	#[test]
+	fn call_function_uses_lexical_not_dynamic_scoping() {
+		let file = parse_file("function inner(): n64 { x } function outer(): n64 { const x: n64 = 5; inner() }");
+		let mut interpreter = Interpreter::new();
+		interpreter.run(&file).unwrap();
+		let error = interpreter.call_function("outer", vec![]).unwrap_err();
+		assert!(
+			error.message.contains("Undefined variable 'x'"),
+			"expected 'outer's local 'x' to stay invisible to 'inner', got: {}",
+			error.message
+		);
+	}
+
+	#[test]
+	fn call_function_restores_caller_scopes_after_parameter_binding_error() {
+		let file = parse_file(
+			"function duplicate(x: n64, x: n64): n64 { x } function outer(): n64 { const y: n64 = 10; duplicate(1, 2); y } function answer(): n64 { 42 }",
+		);
+		let mut interpreter = Interpreter::new();
+		interpreter.run(&file).unwrap();
+		assert!(interpreter.call_function("outer", vec![]).is_err());
+		assert_eq!(interpreter.environment.scopes.len(), 1, "a failed nested call must not leave stray scopes behind");
+		assert_eq!(interpreter.call_function("answer", vec![]).unwrap(), Value::Natural(42));
+	}
+
+	#[test]
+	fn if_else_expression_yields_branch_value() {
+		let file = parse_file("function choose(flag: bool): n64 { const result: n64 = if flag { 1 } else { 2 }; result }");
+		let mut interpreter = Interpreter::new();
+		interpreter.run(&file).unwrap();
+		assert_eq!(interpreter.call_function("choose", vec![Value::Boolean(true)]).unwrap(), Value::Natural(1));
+		assert_eq!(interpreter.call_function("choose", vec![Value::Boolean(false)]).unwrap(), Value::Natural(2));
+	}
+
+	#[test]
+	fn nested_block_scope_shadows_without_leaking() {
+		let file = parse_file(
+			"function shadow(flag: bool): n64 { const x: n64 = 1; const result: n64 = if flag { const x: n64 = 2; x } else { x }; result + x }",
+		);
+		let mut interpreter = Interpreter::new();
+		interpreter.run(&file).unwrap();
+		// flag = true: the if-branch's own 'x' shadows the outer one, but only inside that block.
+		assert_eq!(interpreter.call_function("shadow", vec![Value::Boolean(true)]).unwrap(), Value::Natural(3));
+		// flag = false: the else-branch has no 'x' of its own, so it sees the outer one.
+		assert_eq!(interpreter.call_function("shadow", vec![Value::Boolean(false)]).unwrap(), Value::Natural(2));
+	}
+
+	#[test]
+	fn logical_operators_short_circuit() {
+		// If the right-hand side were evaluated despite the left already deciding the result, the division by
+		// zero in it would turn these into runtime errors instead of the expected booleans.
+		let file = parse_file(
+			"function short_and(): bool { false && 1 / 0 == 0 } function short_or(): bool { true || 1 / 0 == 0 }",
+		);
+		let mut interpreter = Interpreter::new();
+		interpreter.run(&file).unwrap();
+		assert_eq!(interpreter.call_function("short_and", vec![]).unwrap(), Value::Boolean(false));
+		assert_eq!(interpreter.call_function("short_or", vec![]).unwrap(), Value::Boolean(true));
+	}
+}
@@ -2,6 +2,8 @@ use std::fmt::{Display, Formatter};
 
 use log::debug;
 
+use crate::error::{CompileError, CompileErrorKind, Position};
+
 #[repr(u64)]
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum TokenType {
@@ -11,19 +13,36 @@ pub enum TokenType {
 	Function,
 	Const,
 	Var,
+	If,
+	Else,
 	// Type keywords
 	N64,
+	F64,
+	Bool,
+	// Boolean keywords
+	True,
+	False,
 	// Identifiers
 	Identifer,
 	// Literals
 	IntegerLiteral,
+	FloatLiteral,
 	// Symbols
 	OpenParenthesis,
 	CloseParenthesis,
 	OpenBrace,
 	CloseBrace,
 	Colon,
+	Comma,
 	Equals,
+	EqualsEquals,
+	LessThan,
+	GreaterThan,
+	LessThanEquals,
+	GreaterThanEquals,
+	AmpersandAmpersand,
+	PipePipe,
+	Bang,
 	Semicolon,
 	Plus,
 	Minus,
@@ -37,7 +56,13 @@ impl TokenType {
 			"function" => Some(Self::Function),
 			"const" => Some(Self::Const),
 			"var" => Some(Self::Var),
+			"if" => Some(Self::If),
+			"else" => Some(Self::Else),
 			"n64" => Some(Self::N64),
+			"f64" => Some(Self::F64),
+			"bool" => Some(Self::Bool),
+			"true" => Some(Self::True),
+			"false" => Some(Self::False),
 			_ => None,
 		}
 	}
@@ -49,6 +74,7 @@ impl TokenType {
 			'{' => Some(Self::OpenBrace),
 			'}' => Some(Self::CloseBrace),
 			':' => Some(Self::Colon),
+			',' => Some(Self::Comma),
 			'=' => Some(Self::Equals),
 			';' => Some(Self::Semicolon),
 			'+' => Some(Self::Plus),
@@ -62,33 +88,43 @@ impl TokenType {
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub struct Token<'a> {
-	pub type_: TokenType,
-	pub start: usize,
-	pub end:   usize,
-	pub code:  &'a str,
+	pub type_:        TokenType,
+	pub start_offset: usize,
+	pub end_offset:   usize,
+	pub start:        Position,
+	pub end:          Position,
+	pub code:         &'a str,
 }
 
 impl<'a> Token<'a> {
 	pub fn text(&self) -> &'a str {
 		self.code
-			.get(self.start .. self.end)
-			.unwrap_or_else(|| panic!("Invalid token from {} to {}", self.start, self.end))
+			.get(self.start_offset .. self.end_offset)
+			.unwrap_or_else(|| panic!("Invalid token from {} to {}", self.start_offset, self.end_offset))
 	}
 
-	pub fn expect(self, type_: TokenType) -> Result<Self, String> {
+	pub fn expect(self, type_: TokenType) -> Result<Self, CompileError> {
 		debug!("Checking for type {:?}, but has {:?}", type_, self.type_);
 		if self.type_ == type_ {
 			Ok(self)
 		} else {
-			Err(format!("Expected '{:?}' token", type_))
+			Err(CompileError::new(
+				CompileErrorKind::UnexpectedToken,
+				self.start,
+				format!("Expected '{:?}' token", type_),
+			))
 		}
 	}
 
-	pub fn expect_any(self, types: &[TokenType]) -> Result<Self, String> {
+	pub fn expect_any(self, types: &[TokenType]) -> Result<Self, CompileError> {
 		if types.contains(&self.type_) {
 			Ok(self)
 		} else {
-			Err(format!("Expected any of '{:?}' token", types))
+			Err(CompileError::new(
+				CompileErrorKind::UnexpectedToken,
+				self.start,
+				format!("Expected any of '{:?}' token", types),
+			))
 		}
 	}
 }
@@ -103,6 +139,8 @@ impl Display for Token<'_> {
 struct LexerState<'a> {
 	pub code:             &'a str,
 	pub current_position: usize,
+	pub line:             usize,
+	pub column:           usize,
 }
 
 impl<'a> LexerState<'a> {
@@ -121,6 +159,10 @@ impl<'a> LexerState<'a> {
 		self.code.chars().nth(self.current_position + lookahead)
 	}
 
+	pub const fn position(&self) -> Position {
+		Position { line: self.line, column: self.column }
+	}
+
 	pub fn skip_whitespace(&mut self) -> &Self {
 		while !self.is_end() && self.current_char().is_whitespace() {
 			self.advance();
@@ -148,6 +190,12 @@ impl<'a> LexerState<'a> {
 	}
 
 	pub fn advance(&mut self) -> Option<&Self> {
+		if self.current_char() == '\n' {
+			self.line += 1;
+			self.column = 1;
+		} else {
+			self.column += 1;
+		}
 		self.current_position += 1;
 		if self.is_end() {
 			None
@@ -157,8 +205,8 @@ impl<'a> LexerState<'a> {
 	}
 }
 #[allow(clippy::ptr_arg)]
-pub fn lex(code: &str) -> Result<Vec<Token>, String> {
-	let mut state = LexerState { code, current_position: 0 };
+pub fn lex(code: &str) -> Result<Vec<Token>, CompileError> {
+	let mut state = LexerState { code, current_position: 0, line: 1, column: 1 };
 
 	let mut tokens = Vec::new();
 
@@ -169,6 +217,7 @@ pub fn lex(code: &str) -> Result<Vec<Token>, String> {
 		}
 
 		let start = state.current_position;
+		let start_position = state.position();
 		match state.current_char() {
 			'/' => {
 				if let Some(maybe_slash) = state.next_char(1) && maybe_slash == '/' {
@@ -182,16 +231,20 @@ pub fn lex(code: &str) -> Result<Vec<Token>, String> {
 						&state.code[start..state.current_position]
 					);
 					tokens.push(Token {
-						start,
-						end: state.current_position,
+						start_offset: start,
+						end_offset: state.current_position,
+						start: start_position,
+						end: state.position(),
 						code,
 						type_: TokenType::Comment,
 					});
 				} else {
 					state.advance();
 					tokens.push(Token {
-						start,
-						end: start+1,
+						start_offset: start,
+						end_offset: start+1,
+						start: start_position,
+						end: state.position(),
 						code,
 						type_: TokenType::Slash,
 					});
@@ -202,30 +255,101 @@ pub fn lex(code: &str) -> Result<Vec<Token>, String> {
 				if let Some(keyword_type) = TokenType::from_keyword(word) {
 					debug!("Found keyword '{}' of type {:?}", word, keyword_type);
 					tokens.push(Token {
-						start,
-						end: state.current_position,
+						start_offset: start,
+						end_offset: state.current_position,
+						start: start_position,
+						end: state.position(),
 						code,
 						type_: keyword_type,
 					});
 				} else {
 					debug!("Found identifier '{}'", word);
 					tokens.push(Token {
-						start,
-						end: state.current_position,
+						start_offset: start,
+						end_offset: state.current_position,
+						start: start_position,
+						end: state.position(),
 						code,
 						type_: TokenType::Identifer,
 					});
 				}
 			}
 			'0'..='9' => {
-				let number = state.next_number_sequence();
-				// FIXME: Parse base prefixes (0x, 0o, 0b)
-				debug!("Found number '{}'", number);
+				let type_ = if state.current_char() == '0' && matches!(state.next_char(1), Some('x' | 'o' | 'b')) {
+					let base_char = state.next_char(1).unwrap();
+					state.advance(); // consume '0'
+					state.advance(); // consume base character
+					let digits = match base_char {
+						'x' => state.next_of_kind(char::is_ascii_hexdigit),
+						'o' => state.next_of_kind(|character| ('0' ..= '7').contains(character)),
+						'b' => state.next_of_kind(|character| *character == '0' || *character == '1'),
+						_ => unreachable!("matched above"),
+					};
+					if digits.is_empty() {
+						return Err(CompileError::new(
+							CompileErrorKind::InvalidLiteral,
+							start_position,
+							format!("Expected at least one digit after '0{}'", base_char),
+						));
+					}
+					TokenType::IntegerLiteral
+				} else {
+					state.next_number_sequence();
+					if !state.is_end()
+						&& state.current_char() == '.'
+						&& state.next_char(1).is_some_and(|character| character.is_ascii_digit())
+					{
+						state.advance(); // consume '.'
+						state.next_number_sequence();
+						TokenType::FloatLiteral
+					} else {
+						TokenType::IntegerLiteral
+					}
+				};
+				debug!("Found number literal '{}'", &state.code[start..state.current_position]);
+				tokens.push(Token {
+					start_offset: start,
+					end_offset: state.current_position,
+					start: start_position,
+					end: state.position(),
+					code,
+					type_,
+				});
+			}
+			'=' | '<' | '>' | '&' | '|' | '!' => {
+				let symbol_char = state.current_char();
+				let next_char = state.next_char(1);
+				let (consumes_second_char, type_) = match (symbol_char, next_char) {
+					('=', Some('=')) => (true, Some(TokenType::EqualsEquals)),
+					('&', Some('&')) => (true, Some(TokenType::AmpersandAmpersand)),
+					('|', Some('|')) => (true, Some(TokenType::PipePipe)),
+					('<', Some('=')) => (true, Some(TokenType::LessThanEquals)),
+					('>', Some('=')) => (true, Some(TokenType::GreaterThanEquals)),
+					('=', _) => (false, Some(TokenType::Equals)),
+					('<', _) => (false, Some(TokenType::LessThan)),
+					('>', _) => (false, Some(TokenType::GreaterThan)),
+					('!', _) => (false, Some(TokenType::Bang)),
+					_ => (false, None),
+				};
+				let Some(type_) = type_ else {
+					return Err(CompileError::new(
+						CompileErrorKind::UnexpectedToken,
+						start_position,
+						format!("Unexpected token '{}'", symbol_char),
+					));
+				};
+				state.advance();
+				if consumes_second_char {
+					state.advance();
+				}
+				debug!("Found symbol '{}' of type {:?}", &state.code[start..state.current_position], type_);
 				tokens.push(Token {
-					start,
-					end: state.current_position,
+					start_offset: start,
+					end_offset: state.current_position,
+					start: start_position,
+					end: state.position(),
 					code,
-					type_: TokenType::IntegerLiteral,
+					type_,
 				});
 			}
 			_ => {
@@ -235,13 +359,19 @@ pub fn lex(code: &str) -> Result<Vec<Token>, String> {
 				if let Some(symbol_type) = TokenType::from_symbol(symbol_char) {
 					debug!("Found symbol '{}' of type {:?}", symbol_char, symbol_type);
 					tokens.push(Token {
-						start,
-						end: state.current_position,
+						start_offset: start,
+						end_offset: state.current_position,
+						start: start_position,
+						end: state.position(),
 						code,
 						type_: symbol_type,
 					});
 				} else {
-					return Err(format!("Unexpected token '{}'", symbol_char));
+					return Err(CompileError::new(
+						CompileErrorKind::UnexpectedToken,
+						start_position,
+						format!("Unexpected token '{}'", symbol_char),
+					));
 				}
 			}
 		}
@@ -249,3 +379,51 @@ pub fn lex(code: &str) -> Result<Vec<Token>, String> {
 
 	Ok(tokens)
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn lex_types(code: &str) -> Vec<TokenType> {
+		lex(code).unwrap().into_iter().map(|token| token.type_).collect()
+	}
+
+	#[test]
+	fn lex_base_prefixed_integers() {
+		assert_eq!(lex_types("0x1F"), vec![TokenType::IntegerLiteral]);
+		assert_eq!(lex_types("0o17"), vec![TokenType::IntegerLiteral]);
+		assert_eq!(lex_types("0b101"), vec![TokenType::IntegerLiteral]);
+		assert_eq!(lex("0x").unwrap_err().kind, CompileErrorKind::InvalidLiteral);
+		assert_eq!(lex("0b").unwrap_err().kind, CompileErrorKind::InvalidLiteral);
+	}
+
+	#[test]
+	fn lex_float_literals() {
+		assert_eq!(lex_types("3.14"), vec![TokenType::FloatLiteral]);
+		assert_eq!(lex_types("0.5"), vec![TokenType::FloatLiteral]);
+		assert_eq!(lex_types("42"), vec![TokenType::IntegerLiteral]);
+		assert_eq!(lex_types("3.14 + 2"), vec![TokenType::FloatLiteral, TokenType::Plus, TokenType::IntegerLiteral]);
+	}
+
+	#[test]
+	fn token_positions_track_lines_and_columns_across_comments() {
+		let code = "const x: n64 = 1;\n// a comment\nconst y: f64 = 0x2A;";
+		let tokens = lex(code).unwrap();
+
+		let first_const = tokens[0];
+		assert_eq!(first_const.start, Position { line: 1, column: 1 });
+		assert_eq!(first_const.end, Position { line: 1, column: 6 });
+
+		let comment = tokens.iter().find(|token| token.type_ == TokenType::Comment).unwrap();
+		assert_eq!(comment.start, Position { line: 2, column: 1 });
+
+		// The second 'const', on line 3, must have its column reset to 1 after the comment's newline.
+		let second_const = tokens.iter().rev().find(|token| token.type_ == TokenType::Const).unwrap();
+		assert_eq!(second_const.start, Position { line: 3, column: 1 });
+
+		// The base-prefixed literal's end column is one past the last of the four characters of '0x2A', where the ';' starts.
+		let hex_literal = tokens.iter().rev().find(|token| token.type_ == TokenType::IntegerLiteral).unwrap();
+		assert_eq!(hex_literal.start, Position { line: 3, column: 16 });
+		assert_eq!(hex_literal.end, Position { line: 3, column: 20 });
+	}
+}
@@ -6,14 +6,31 @@ use std::env::args;
 use std::fs::read_to_string;
 
 mod ast;
+mod codegen;
+mod error;
+mod interpreter;
 mod lexer;
 mod parser;
 
+use codegen::{CGenerator, Generator};
+use interpreter::Interpreter;
+
 fn main() {
 	simple_logger::init_with_level(Level::Debug).expect("Couldn't initialize logger");
 
-	let mut args = args();
-	let filename = args.nth(1).expect("No file to parse given");
+	let mut filename = None;
+	let mut backend = None;
+	let mut interpret = false;
+	let mut arguments = args().skip(1);
+	while let Some(argument) = arguments.next() {
+		match argument.as_str() {
+			"--backend" => backend = Some(arguments.next().expect("--backend requires a value")),
+			"--interpret" => interpret = true,
+			_ => filename = Some(argument),
+		}
+	}
+	let filename = filename.expect("No file to parse given");
+
 	let code = read_to_string(&filename).expect("I/O error while reading from file");
 	let maybe_tokens = lexer::lex(&code);
 	match maybe_tokens {
@@ -26,9 +43,23 @@ fn main() {
 					.collect::<Vec<String>>()
 					.join(" ")
 			);
-			match parser::parse(&tokens) {
-				Ok(ast) => println!("{:#?}", ast),
-				Err(why) => panic!("Error while parsing: {:?}", why),
+			match parser::parse(tokens) {
+				Ok(ast) =>
+					if interpret {
+						if let Err(why) = Interpreter::new().run(&ast) {
+							panic!("Error while interpreting: {}", why);
+						}
+					} else {
+						match backend.as_deref() {
+							Some("c") => match CGenerator::default().generate(&ast) {
+								Ok(source) => println!("{}", source),
+								Err(why) => panic!("Error while generating code: {}", why),
+							},
+							Some(other) => panic!("Unknown backend '{}'", other),
+							None => println!("{:#?}", ast),
+						}
+					},
+				Err(why) => panic!("Error while parsing: {}", why),
 			}
 		}
 		Err(why) => panic!("Error while lexing file {}: {}", filename, why),
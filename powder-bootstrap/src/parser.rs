@@ -1,8 +1,10 @@
 use log::debug;
 
 use crate::ast::{
-	Ast, BinaryOperator, Block, Definition, Expression, File, Function, Statement, Type, UnaryOperator, VariableKind,
+	BinaryOperator, Block, Definition, Expression, File, Function, Parameter, Statement, Type, UnaryOperator,
+	VariableKind,
 };
+use crate::error::{CompileError, CompileErrorKind, Position};
 use crate::lexer::{Token, TokenType};
 
 // TODO: Include the code string with this struct. That makes it self-referential, though, so we would need ouboros (?).
@@ -29,59 +31,28 @@ impl<'a> TokenStream<'a> {
 		self.index >= self.len()
 	}
 
-	pub fn next(&mut self, error_message: &str) -> Result<Token<'a>, String> {
+	/// The position to blame when a lookahead past the end of the stream fails.
+	fn eof_position(&self) -> Position {
+		self.tokens.last().map_or_else(Position::start, |token| token.end)
+	}
+
+	pub fn next(&mut self, error_message: &str) -> Result<Token<'a>, CompileError> {
 		if self.is_end() {
-			Err(error_message.to_owned())
+			Err(CompileError::new(CompileErrorKind::UnexpectedEof, self.eof_position(), error_message))
 		} else {
 			self.index += 1;
 			Ok(self.tokens[self.index - 1])
 		}
 	}
 
-	pub fn lookahead(&self, amount: usize, error_message: &str) -> Result<&[Token<'a>], String> {
-		self.tokens.get(self.index .. self.index + amount).ok_or_else(|| error_message.to_owned())
-	}
-
-	#[allow(clippy::cast_possible_wrap)]
-	pub fn backtrack(&mut self, amount: usize, error_message: &str) -> Result<(), String> {
-		if (self.index as isize - amount as isize) < 0 {
-			Err(error_message.to_owned())
-		} else {
-			self.index -= amount;
-			Ok(())
-		}
-	}
-
-	pub fn make_substream(&self) -> Self {
-		Self { tokens: &self.tokens[self.index ..], index: 0 }
-	}
-
-	pub fn limit_to_first(&mut self, type_: TokenType) -> &Self {
-		let mut current_index = self.index;
-		while let Some(next_token) = self.tokens.get(current_index ..= current_index) {
-			if next_token[0].type_ == type_ {
-				break;
-			}
-			current_index += 1;
-		}
-		self.tokens = &self.tokens[.. current_index];
-
-		self
-	}
-
-	pub fn advance_past_other(&mut self, other_stream: &Self) -> Result<&Self, String> {
-		let other_token = other_stream.lookahead(1, "Expected any token")?[0];
-		while let Ok(next_token) = self.next("") {
-			if next_token == other_token {
-				break;
-			}
-		}
-
-		Ok(self)
+	pub fn lookahead(&self, amount: usize, error_message: &str) -> Result<&[Token<'a>], CompileError> {
+		self.tokens
+			.get(self.index .. self.index + amount)
+			.ok_or_else(|| CompileError::new(CompileErrorKind::UnexpectedEof, self.eof_position(), error_message))
 	}
 }
 
-pub fn parse(tokens: Vec<Token>) -> Result<Box<dyn Ast>, String> {
+pub fn parse(tokens: Vec<Token>) -> Result<File, CompileError> {
 	let mut definitions = Vec::<Definition>::new();
 
 	let stripped_tokens = TokenStream::strip_comments(tokens);
@@ -91,62 +62,140 @@ pub fn parse(tokens: Vec<Token>) -> Result<Box<dyn Ast>, String> {
 		definitions.push(parse_definition(&mut token_stream)?);
 	}
 
-	Ok(Box::new(File { definitions }))
+	Ok(File { definitions })
 }
 
-fn parse_definition(token_iterator: &mut TokenStream) -> Result<Definition, String> {
+fn parse_definition(token_iterator: &mut TokenStream) -> Result<Definition, CompileError> {
 	let first_token = token_iterator.lookahead(1, "Expected definition")?[0];
 	if first_token.expect(TokenType::Function).is_ok() {
 		token_iterator.next("")?;
 		let function_name = token_iterator.next("Expected function name")?.expect(TokenType::Identifer)?;
 		token_iterator.next("Expected '('")?.expect(TokenType::OpenParenthesis)?;
-		// TODO: Parse arguments
+		let parameters = parse_parameters(token_iterator)?;
 		token_iterator.next("Expected ')'")?.expect(TokenType::CloseParenthesis)?;
 
+		let return_type = if token_iterator.lookahead(1, "").map_or(false, |tokens| tokens[0].type_ == TokenType::Colon)
+		{
+			token_iterator.next("")?;
+			Some(parse_type(token_iterator)?)
+		} else {
+			None
+		};
+
 		let function_body = parse_block(token_iterator)?;
 
 		token_iterator.next("Expected '}'")?.expect(TokenType::CloseBrace)?;
 
-		Ok(Definition::Function(Function { name: function_name.text().to_string(), body: function_body }))
+		Ok(Definition::Function(Function {
+			name: function_name.text().to_string(),
+			parameters,
+			return_type,
+			body: function_body,
+		}))
 	} else {
 		Ok(Definition::Statement(parse_statement(token_iterator)?))
 	}
 }
 
-fn parse_block(token_iterator: &mut TokenStream) -> Result<Block, String> {
+fn parse_parameters(token_iterator: &mut TokenStream) -> Result<Vec<Parameter>, CompileError> {
+	let mut parameters = Vec::new();
+
+	while token_iterator.lookahead(1, "Expected ')' or parameter")?[0].type_ != TokenType::CloseParenthesis {
+		let name = token_iterator.next("Expected parameter name")?.expect(TokenType::Identifer)?;
+		token_iterator.next("Expected ':'")?.expect(TokenType::Colon)?;
+		let type_ = parse_type(token_iterator)?;
+		parameters.push(Parameter { name: name.text().to_string(), type_ });
+
+		if token_iterator.lookahead(1, "Expected ',' or ')'")?[0].type_ == TokenType::Comma {
+			token_iterator.next("")?;
+		} else {
+			break;
+		}
+	}
+
+	Ok(parameters)
+}
+
+fn parse_arguments(token_iterator: &mut TokenStream) -> Result<Vec<Expression>, CompileError> {
+	let mut arguments = Vec::new();
+
+	while token_iterator.lookahead(1, "Expected ')' or argument")?[0].type_ != TokenType::CloseParenthesis {
+		arguments.push(parse_expression(token_iterator)?);
+
+		if token_iterator.lookahead(1, "Expected ',' or ')'")?[0].type_ == TokenType::Comma {
+			token_iterator.next("")?;
+		} else {
+			break;
+		}
+	}
+
+	Ok(arguments)
+}
+
+fn parse_type(token_iterator: &mut TokenStream) -> Result<Type, CompileError> {
+	let token = token_iterator.next("Expected type")?;
+	match token.type_ {
+		TokenType::N64 => Ok(Type::N64),
+		TokenType::F64 => Ok(Type::F64),
+		TokenType::Bool => Ok(Type::Bool),
+		_ => Err(CompileError::new(
+			CompileErrorKind::UnexpectedToken,
+			token.start,
+			format!("Expected a type, found {:?}", token.type_),
+		)),
+	}
+}
+
+fn parse_block(token_iterator: &mut TokenStream) -> Result<Block, CompileError> {
 	token_iterator.next("Expected '{'")?.expect(TokenType::OpenBrace)?;
 
 	let mut statements = Vec::<Statement>::new();
+	let mut value = None;
+
+	loop {
+		let next = token_iterator.lookahead(1, "Expected statement or '}'")?[0];
+		if next.type_ == TokenType::CloseBrace {
+			break;
+		}
+
+		if next.type_ == TokenType::Var || next.type_ == TokenType::Const {
+			statements.push(parse_statement(token_iterator)?);
+			continue;
+		}
 
-	let mut maybe_peeked_next = token_iterator.lookahead(1, "Expected statement").map(|peeked_next| peeked_next[0]);
-	while maybe_peeked_next.map_or(false, |peeked_next| peeked_next.type_ != TokenType::CloseBrace) {
-		statements.push(parse_statement(token_iterator)?);
-		maybe_peeked_next = token_iterator.lookahead(1, "Expected statement").map(|peeked_next| peeked_next[0]);
+		// No statement keyword matched, so this must be an expression: either another expression statement
+		// terminated by ';', or the block's trailing value if it is followed directly by '}'.
+		let expression = parse_expression(token_iterator)?;
+		let next = token_iterator.lookahead(1, "Expected ';' or '}'")?[0];
+		if next.type_ == TokenType::Semicolon {
+			token_iterator.next("")?;
+			statements.push(Statement::Expression(expression));
+		} else {
+			value = Some(expression);
+			break;
+		}
 	}
 
-	// TODO: Parse last expression
-	Ok(Block { statements, value: None })
+	Ok(Block { statements, value })
 }
 
-fn parse_statement(token_iterator: &mut TokenStream) -> Result<Statement, String> {
+fn parse_statement(token_iterator: &mut TokenStream) -> Result<Statement, CompileError> {
 	// TODO: Parse other statement types
 	let kind = token_iterator.next("Expected statement")?.expect_any(&[TokenType::Var, TokenType::Const])?;
 
 	let identifier = token_iterator.next("Expected identifier")?.expect(TokenType::Identifer)?;
 	token_iterator.next("Expected ':'")?.expect(TokenType::Colon)?;
-	// TODO: Allow any type
-	let _type = token_iterator.next("Expected type")?.expect(TokenType::N64)?;
+	let type_ = parse_type(token_iterator)?;
 
 	// Get the initializer if it exists
 	let initializer =
 		if let Ok(_equals) = token_iterator.lookahead(1, "Expected '=' or ';'")?[0].expect(TokenType::Equals) {
 			token_iterator.next("")?;
-			let mut expression_stream = token_iterator.make_substream();
-			expression_stream.limit_to_first(TokenType::Semicolon);
-			// debug!("{:#?}", expression_stream);
-			let expression = parse_expression(&mut expression_stream)?;
-			expression_stream.backtrack(1, ":yaksplode:")?;
-			token_iterator.advance_past_other(&expression_stream)?;
+			// parse_expression naturally stops at the trailing ';', which the precedence-climbing loop doesn't
+			// recognize as a binary operator, so no artificial bound on how far it's allowed to look is needed;
+			// that used to be done via a substream truncated at the first ';', which broke as soon as the
+			// initializer could itself contain a ';' inside a nested block, e.g. an if-expression.
+			let expression = parse_expression(token_iterator)?;
 			token_iterator.next("Expected ';'")?.expect(TokenType::Semicolon)?;
 			Some(expression)
 		} else {
@@ -155,53 +204,64 @@ fn parse_statement(token_iterator: &mut TokenStream) -> Result<Statement, String
 		};
 
 	Ok(Statement::VariableDeclaration {
-		kind:          if kind.type_ == TokenType::Const { VariableKind::Immutable } else { VariableKind::Mutable },
-		name:          identifier.text().to_string(),
-		type_:         Type::N64,
+		kind: if kind.type_ == TokenType::Const { VariableKind::Immutable } else { VariableKind::Mutable },
+		name: identifier.text().to_string(),
+		type_,
 		initial_value: initializer,
 	})
 }
 
-fn parse_expression(token_iterator: &mut TokenStream) -> Result<Expression, String> {
-	parse_binary_operation(token_iterator, parse_term, &[TokenType::Plus, TokenType::Minus])
+/// The left/right binding power of a binary operator, used to drive precedence-climbing in [`parse_expr`].
+/// A higher number binds tighter. Left-associative operators use `right_bp = left_bp + 1`.
+const fn binding_power(token_type: TokenType) -> Option<(u8, u8)> {
+	match token_type {
+		TokenType::PipePipe => Some((1, 2)),
+		TokenType::AmpersandAmpersand => Some((2, 3)),
+		TokenType::EqualsEquals
+		| TokenType::LessThan
+		| TokenType::GreaterThan
+		| TokenType::LessThanEquals
+		| TokenType::GreaterThanEquals => Some((3, 4)),
+		TokenType::Plus | TokenType::Minus => Some((4, 5)),
+		TokenType::Star | TokenType::Slash => Some((5, 6)),
+		_ => None,
+	}
 }
 
-fn parse_binary_operation(
-	token_iterator: &mut TokenStream,
-	sub_operation_parser: fn(&mut TokenStream) -> Result<Expression, String>,
-	operators: &[TokenType],
-) -> Result<Expression, String> {
-	let mut lhs = sub_operation_parser(token_iterator)?;
-
-	let mut maybe_operator =
-		token_iterator.lookahead(1, &format!("Expected any of {:?}", operators)).map(|tokens| tokens[0]);
-	while let Ok(operator) = maybe_operator {
-		if !operators.contains(&operator.type_) {
+/// The binding power a prefix unary operator's operand is parsed at; binds tighter than any binary operator.
+const UNARY_BINDING_POWER: u8 = 7;
+
+fn parse_expression(token_iterator: &mut TokenStream) -> Result<Expression, CompileError> {
+	parse_expr(token_iterator, 0)
+}
+
+/// Precedence-climbing expression parser: parses a prefix operand, then repeatedly consumes binary operators
+/// whose left binding power is at least `min_bp`, recursing with that operator's right binding power.
+fn parse_expr(token_iterator: &mut TokenStream, min_bp: u8) -> Result<Expression, CompileError> {
+	let mut lhs = parse_factor(token_iterator)?;
+
+	while let Ok(operator) = token_iterator.lookahead(1, "").map(|tokens| tokens[0]) {
+		let Some((left_bp, right_bp)) = binding_power(operator.type_) else { break };
+		if left_bp < min_bp {
 			break;
 		}
 		token_iterator.next("")?;
-		let rhs = sub_operation_parser(token_iterator)?;
+		let rhs = parse_expr(token_iterator, right_bp)?;
 		lhs = Expression::BinaryOperation {
 			operator: BinaryOperator::from_token_type(operator.type_).unwrap(),
 			lhs:      Box::new(lhs),
 			rhs:      Box::new(rhs),
 		};
-		maybe_operator =
-			token_iterator.lookahead(1, &format!("Expected any of {:?}", operators)).map(|tokens| tokens[0]);
 	}
 	Ok(lhs)
 }
 
-fn parse_term(token_iterator: &mut TokenStream) -> Result<Expression, String> {
-	parse_binary_operation(token_iterator, parse_factor, &[TokenType::Star, TokenType::Slash])
-}
-
-fn parse_factor(token_iterator: &mut TokenStream) -> Result<Expression, String> {
+fn parse_factor(token_iterator: &mut TokenStream) -> Result<Expression, CompileError> {
 	let token = token_iterator.lookahead(1, "Expected expression")?[0];
 	match token.type_ {
-		TokenType::Plus | TokenType::Minus => {
+		TokenType::Plus | TokenType::Minus | TokenType::Bang => {
 			token_iterator.next("")?;
-			let unary_operand = parse_factor(token_iterator)?;
+			let unary_operand = parse_expr(token_iterator, UNARY_BINDING_POWER)?;
 			Ok(Expression::UnaryOperation(
 				UnaryOperator::from_token_type(token.type_).unwrap(),
 				Box::new(unary_operand),
@@ -209,21 +269,92 @@ fn parse_factor(token_iterator: &mut TokenStream) -> Result<Expression, String>
 		},
 		TokenType::IntegerLiteral => {
 			let number_literal = token_iterator.next("")?.expect(TokenType::IntegerLiteral)?;
-			// TODO: Use our own integer parser
-			let value: i128 =
-				number_literal.text().parse().map_err(|err| format!("Invalid number literal '{}'", err))?;
+			let value = parse_integer_literal(number_literal.text()).map_err(|message| {
+				CompileError::new(CompileErrorKind::InvalidLiteral, number_literal.start, message)
+			})?;
 			Ok(Expression::NaturalLiteral(value))
 		},
-		_ => Err(format!("Unknown start of expression '{:?}'", token.type_)),
+		TokenType::FloatLiteral => {
+			let float_literal = token_iterator.next("")?.expect(TokenType::FloatLiteral)?;
+			let value: f64 = float_literal.text().parse().map_err(|err| {
+				CompileError::new(
+					CompileErrorKind::InvalidLiteral,
+					float_literal.start,
+					format!("Invalid float literal '{}'", err),
+				)
+			})?;
+			Ok(Expression::FloatLiteral(value))
+		},
+		TokenType::True => {
+			token_iterator.next("")?;
+			Ok(Expression::BooleanLiteral(true))
+		},
+		TokenType::False => {
+			token_iterator.next("")?;
+			Ok(Expression::BooleanLiteral(false))
+		},
+		TokenType::If => parse_if(token_iterator),
+		TokenType::Identifer => {
+			token_iterator.next("")?;
+			let name = token.text().to_string();
+			if token_iterator.lookahead(1, "").map_or(false, |tokens| tokens[0].type_ == TokenType::OpenParenthesis) {
+				token_iterator.next("")?;
+				let arguments = parse_arguments(token_iterator)?;
+				token_iterator.next("Expected ')'")?.expect(TokenType::CloseParenthesis)?;
+				Ok(Expression::Call { callee: name, arguments })
+			} else {
+				Ok(Expression::Variable(name))
+			}
+		},
+		_ => Err(CompileError::new(
+			CompileErrorKind::UnexpectedToken,
+			token.start,
+			format!("Unknown start of expression '{:?}'", token.type_),
+		)),
 	}
 }
 
-#[cfg(test)]
-mod test {
+/// Parse an integer literal's text, interpreting a `0x`/`0o`/`0b` prefix as the literal's radix.
+fn parse_integer_literal(text: &str) -> Result<i128, String> {
+	let (radix, digits) = if let Some(digits) = text.strip_prefix("0x") {
+		(16, digits)
+	} else if let Some(digits) = text.strip_prefix("0o") {
+		(8, digits)
+	} else if let Some(digits) = text.strip_prefix("0b") {
+		(2, digits)
+	} else {
+		(10, text)
+	};
+	i128::from_str_radix(digits, radix).map_err(|err| format!("Invalid number literal '{}': {}", text, err))
+}
+
+fn parse_if(token_iterator: &mut TokenStream) -> Result<Expression, CompileError> {
+	token_iterator.next("Expected 'if'")?.expect(TokenType::If)?;
+	let condition = parse_expression(token_iterator)?;
+
+	let then_block = parse_block(token_iterator)?;
+	token_iterator.next("Expected '}'")?.expect(TokenType::CloseBrace)?;
+
+	let else_block = if token_iterator.lookahead(1, "").map_or(false, |tokens| tokens[0].type_ == TokenType::Else) {
+		token_iterator.next("")?;
+		let block = parse_block(token_iterator)?;
+		token_iterator.next("Expected '}'")?.expect(TokenType::CloseBrace)?;
+		Some(Box::new(block))
+	} else {
+		None
+	};
+
+	Ok(Expression::If { condition: Box::new(condition), then_block: Box::new(then_block), else_block })
+}
+
+// Gated behind a feature so a plain `cargo test` on stable, which cannot compile `extern crate test`, isn't
+// broken by it; run with `cargo +nightly test --features nightly-bench` to exercise the benchmark.
+#[cfg(all(test, feature = "nightly-bench"))]
+mod bench {
 	extern crate test;
 	use test::Bencher;
 
-	use super::*;
+	use super::parse;
 	use crate::lexer;
 
 	#[bench]
@@ -232,6 +363,12 @@ mod test {
 		let tokens = lexer::lex(&file_contents).unwrap();
 		bencher.iter(move || parse(tokens.clone()));
 	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::lexer;
 
 	fn evaluate_expression(expression: Expression) -> i128 {
 		match expression {
@@ -252,7 +389,7 @@ mod test {
 
 	static mut TOKEN_STREAMS: Vec<Vec<Token>> = Vec::<Vec<Token>>::new();
 
-	fn create_tokens(code: &'static str) -> Result<TokenStream<'static>, String> {
+	fn create_tokens(code: &'static str) -> Result<TokenStream<'static>, CompileError> {
 		unsafe {
 			TOKEN_STREAMS.push(lexer::lex(code)?);
 			Ok(TokenStream::new(&TOKEN_STREAMS[TOKEN_STREAMS.len() - 1]))
@@ -260,7 +397,7 @@ mod test {
 	}
 
 	#[test]
-	fn test_parse_expression() -> Result<(), String> {
+	fn test_parse_expression() -> Result<(), CompileError> {
 		assert_eq!(evaluate_expression(parse_expression(&mut create_tokens("-15")?)?), -15);
 		assert_eq!(evaluate_expression(parse_expression(&mut create_tokens("0 + 7 * 3 + 5")?)?), 26);
 		assert_eq!(evaluate_expression(parse_expression(&mut create_tokens("1 - 5")?)?), -4);
@@ -270,4 +407,31 @@ mod test {
 
 		Ok(())
 	}
+
+	#[test]
+	fn logical_operators_respect_precedence() -> Result<(), CompileError> {
+		// '&&' binds tighter than '||', so this must parse as 'a || (b && c)', not '(a || b) && c'.
+		let expression = parse_expression(&mut create_tokens("a || b && c")?)?;
+		match expression {
+			Expression::BinaryOperation { operator: BinaryOperator::Or, lhs, rhs } => {
+				assert!(matches!(*lhs, Expression::Variable(name) if name == "a"));
+				assert!(matches!(*rhs, Expression::BinaryOperation { operator: BinaryOperator::And, .. }));
+			},
+			other => panic!("Expected top-level '||', got {:?}", other),
+		}
+
+		Ok(())
+	}
+
+	#[test]
+	fn compile_error_position_tracks_the_offending_line() -> Result<(), CompileError> {
+		// 'bogus' is not a type keyword, so parse_type must report the error right where it starts: line 2,
+		// column 10, not wherever the file or the enclosing statement began.
+		let code = "const x: n64 = 1;\nconst y: bogus = 2;";
+		let tokens = lexer::lex(code)?;
+		let error = parse(tokens).unwrap_err();
+		assert_eq!(error.position, Position { line: 2, column: 10 });
+
+		Ok(())
+	}
 }